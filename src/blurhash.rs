@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::convert::TryFrom;
 use std::f64::consts::PI;
+use std::num::NonZeroU32;
 use thiserror::*;
 
 
@@ -12,6 +13,16 @@ pub enum EncodingError {
     BytesPerPixelMismatch,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum DecodingError {
+    #[error("hash is too short to be a valid BlurHash")]
+    HashTooShort,
+    #[error("hash contains a character outside the base83 alphabet")]
+    InvalidCharacter,
+    #[error("hash length does not match the component count encoded in it")]
+    LengthMismatch,
+}
+
 fn sign_pow(value: f64, exp: f64) -> f64 {
     value.abs().powf(exp).copysign(value)
 }
@@ -125,6 +136,271 @@ pub fn encode(
     Ok(hash)
 }
 
+// Decode
+
+/// Reconstructs an RGBA preview image from a BlurHash string.
+///
+/// This is the inverse of [`encode`]: `width`/`height` set the output
+/// resolution, and `punch` scales the AC contribution (`1.0` reproduces the
+/// original encoding behaviour).
+pub fn decode(
+    hash: &str,
+    width: usize,
+    height: usize,
+    punch: f64,
+) -> Result<Vec<u8>, DecodingError> {
+    if hash.len() < 6 {
+        return Err(DecodingError::HashTooShort);
+    }
+
+    // The base83 alphabet is ASCII-only; byte-slicing below assumes one byte per
+    // character, which a multibyte UTF-8 char would violate.
+    if !hash.is_ascii() {
+        return Err(DecodingError::InvalidCharacter);
+    }
+
+    let size_flag = decode_base83_string(&hash[0..1])?;
+    let num_x = size_flag % 9 + 1;
+    let num_y = size_flag / 9 + 1;
+
+    let expected_length = 4 + 2 * (num_x * num_y - 1) + 2;
+    if hash.len() != expected_length {
+        return Err(DecodingError::LengthMismatch);
+    }
+
+    let quantised_maximum_value = decode_base83_string(&hash[1..2])?;
+    let maximum_value = ((quantised_maximum_value + 1) as f64 / 166f64) * punch;
+
+    let mut colors: Vec<[f64; 3]> = Vec::with_capacity(num_x * num_y);
+
+    let dc_value = decode_base83_string(&hash[2..6])?;
+    colors.push([
+        srgb_to_linear(dc_value >> 16),
+        srgb_to_linear((dc_value >> 8) & 255),
+        srgb_to_linear(dc_value & 255),
+    ]);
+
+    for i in 1..(num_x * num_y) {
+        let start = 4 + i * 2;
+        let value = decode_base83_string(&hash[start..start + 2])?;
+        let quant_r = value / (19 * 19);
+        let quant_g = (value / 19) % 19;
+        let quant_b = value % 19;
+        colors.push([
+            sign_pow((quant_r as f64 - 9f64) / 9f64, 2.0) * maximum_value,
+            sign_pow((quant_g as f64 - 9f64) / 9f64, 2.0) * maximum_value,
+            sign_pow((quant_b as f64 - 9f64) / 9f64, 2.0) * maximum_value,
+        ]);
+    }
+
+    let mut pixels = vec![0u8; width * height * 4];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut pixel = [0f64; 3];
+
+            for j in 0..num_y {
+                for i in 0..num_x {
+                    let basis = f64::cos(PI * x as f64 * i as f64 / width as f64)
+                        * f64::cos(PI * y as f64 * j as f64 / height as f64);
+                    let color = colors[i + j * num_x];
+                    pixel[0] += color[0] * basis;
+                    pixel[1] += color[1] * basis;
+                    pixel[2] += color[2] * basis;
+                }
+            }
+
+            let offset = (y * width + x) * 4;
+            pixels[offset] = linear_to_srgb(pixel[0]) as u8;
+            pixels[offset + 1] = linear_to_srgb(pixel[1]) as u8;
+            pixels[offset + 2] = linear_to_srgb(pixel[2]) as u8;
+            pixels[offset + 3] = 255;
+        }
+    }
+
+    Ok(pixels)
+}
+
+fn decode_base83_char(c: char) -> Result<usize, DecodingError> {
+    ENCODE_CHARACTERS
+        .iter()
+        .position(|&candidate| candidate == c)
+        .ok_or(DecodingError::InvalidCharacter)
+}
+
+fn decode_base83_string(s: &str) -> Result<usize, DecodingError> {
+    s.chars()
+        .try_fold(0usize, |acc, c| Ok(acc * 83 + decode_base83_char(c)?))
+}
+
+// Reusable encoder
+
+/// A reusable BlurHash encoder for a fixed `(components_x, components_y, width, height)`
+/// tuple.
+///
+/// Plain [`encode`] recomputes the sRGB-to-linear conversion (via `powf`) and the cosine
+/// basis functions on every call. When encoding many images that share the same dimensions
+/// and component counts, build one `Encoder` and reuse it: the lookup table and basis
+/// factors are computed once in [`Encoder::new`], and [`Encoder::encode`] only does the
+/// weighted accumulation.
+pub struct Encoder {
+    cx: usize,
+    cy: usize,
+    width: usize,
+    height: usize,
+    srgb_to_linear_table: [f64; 256],
+    cos_x: Vec<Vec<f64>>,
+    cos_y: Vec<Vec<f64>>,
+}
+
+impl Encoder {
+    pub fn new(cx: usize, cy: usize, width: usize, height: usize) -> Result<Self, EncodingError> {
+        if cx < 1 || cx > 9 || cy < 1 || cy > 9 {
+            return Err(EncodingError::ComponentsNumberInvalid);
+        }
+
+        let mut srgb_to_linear_table = [0f64; 256];
+        for (value, entry) in srgb_to_linear_table.iter_mut().enumerate() {
+            *entry = srgb_to_linear(value);
+        }
+
+        let cos_x = (0..cx)
+            .map(|i| {
+                (0..width)
+                    .map(|x| f64::cos((PI * i as f64 * x as f64) / width as f64))
+                    .collect()
+            })
+            .collect();
+        let cos_y = (0..cy)
+            .map(|j| {
+                (0..height)
+                    .map(|y| f64::cos((PI * j as f64 * y as f64) / height as f64))
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            cx,
+            cy,
+            width,
+            height,
+            srgb_to_linear_table,
+            cos_x,
+            cos_y,
+        })
+    }
+
+    /// Encodes an RGBA buffer matching this encoder's `width`/`height` into a BlurHash
+    /// string.
+    ///
+    /// `skip` samples only every Nth pixel in both dimensions while accumulating the basis
+    /// functions, trading a small amount of accuracy for a large speedup on big inputs.
+    /// `skip == 1` reproduces the same output as summing every pixel.
+    pub fn encode(&self, pixels: &[u8], skip: NonZeroU32) -> Result<String, EncodingError> {
+        if self.width * self.height * 4 != pixels.len() {
+            return Err(EncodingError::BytesPerPixelMismatch);
+        }
+
+        let bytes_per_row = self.width * 4;
+        let bytes_per_pixel = 4;
+
+        let mut dc: [f64; 3] = [0., 0., 0.];
+        let mut ac: Vec<[f64; 3]> = Vec::with_capacity(self.cx * self.cy - 1);
+
+        for j in 0..self.cy {
+            for i in 0..self.cx {
+                let normalisation = if i == 0 && j == 0 { 1f64 } else { 2f64 };
+                let factor = self.multiply_basis_function(
+                    pixels,
+                    bytes_per_row,
+                    bytes_per_pixel,
+                    i,
+                    j,
+                    normalisation,
+                    skip,
+                );
+
+                if i == 0 && j == 0 {
+                    dc = factor;
+                } else {
+                    ac.push(factor);
+                }
+            }
+        }
+
+        let mut hash = String::new();
+
+        let size_flag = (self.cx - 1) + (self.cy - 1) * 9;
+        hash += &encode_base83_string(size_flag, 1);
+
+        let maximum_value: f64;
+
+        if !ac.is_empty() {
+            let actual_maximum_value = ac
+                .iter()
+                .map(|[a, b, c]| f64::max(f64::max(f64::abs(*a), f64::abs(*b)), f64::abs(*c)))
+                .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .unwrap();
+            let quantised_maximum_value = usize::max(
+                0,
+                usize::min(82, f64::floor(actual_maximum_value * 166f64 - 0.5) as usize),
+            );
+            maximum_value = ((quantised_maximum_value + 1) as f64) / 166f64;
+            hash += &encode_base83_string(quantised_maximum_value, 1);
+        } else {
+            maximum_value = 1f64;
+            hash += &encode_base83_string(0, 1);
+        }
+
+        hash += &encode_base83_string(encode_dc(dc), 4);
+
+        for factor in ac {
+            hash += &encode_base83_string(encode_ac(factor, maximum_value), 2);
+        }
+
+        Ok(hash)
+    }
+
+    fn multiply_basis_function(
+        &self,
+        pixels: &[u8],
+        bytes_per_row: usize,
+        bytes_per_pixel: usize,
+        cx_i: usize,
+        cy_j: usize,
+        normalisation: f64,
+        skip: NonZeroU32,
+    ) -> [f64; 3] {
+        let mut r = 0f64;
+        let mut g = 0f64;
+        let mut b = 0f64;
+        let mut samples = 0usize;
+
+        let cos_x = &self.cos_x[cx_i];
+        let cos_y = &self.cos_y[cy_j];
+        let step = skip.get() as usize;
+
+        let mut x = 0;
+        while x < self.width {
+            let basis_x = normalisation * cos_x[x];
+            let mut y = 0;
+            while y < self.height {
+                let basis = basis_x * cos_y[y];
+                let offset = bytes_per_pixel * x + y * bytes_per_row;
+                r += basis * self.srgb_to_linear_table[pixels[offset] as usize];
+                g += basis * self.srgb_to_linear_table[pixels[offset + 1] as usize];
+                b += basis * self.srgb_to_linear_table[pixels[offset + 2] as usize];
+                samples += 1;
+                y += step;
+            }
+            x += step;
+        }
+
+        let scale = 1f64 / (samples as f64);
+        [r * scale, g * scale, b * scale]
+    }
+}
+
 fn multiply_basis_function<F>(
     pixels: &[u8],
     width: usize,
@@ -1,10 +1,14 @@
 use anyhow::Result;
 use clap::Parser;
+use ffmpeg_next as ffmpeg;
 use futures::future::join_all;
 use imx::{get_image_dimensions, is_image_file, process_jxl_file};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Semaphore;
+use tokio::sync::{Mutex, Semaphore};
 use xio::{read_file_content, walk_directory, write_to_file};
 
 mod blurhash;
@@ -23,29 +27,316 @@ struct Args {
     /// Number of Y components for BlurHash
     #[arg(short = 'y', long, default_value_t = 3)]
     components_y: usize,
+
+    /// Sample only every Nth pixel in both dimensions when encoding, trading accuracy
+    /// for speed on large images
+    #[arg(long, default_value_t = NonZeroU32::new(1).unwrap())]
+    skip: NonZeroU32,
+
+    /// Decode `.bh` files back into small preview PNGs instead of encoding
+    #[arg(long)]
+    decode: bool,
+
+    /// Width of the decoded preview image
+    #[arg(long, default_value_t = 32)]
+    decode_width: usize,
+
+    /// Height of the decoded preview image
+    #[arg(long, default_value_t = 32)]
+    decode_height: usize,
+
+    /// Punch factor applied to the decoded AC components
+    #[arg(long, default_value_t = 1.0)]
+    punch: f64,
+
+    /// Fractional position (0.0-1.0) into a video's duration to grab the preview frame from
+    #[arg(long, default_value_t = 0.1)]
+    frame_time: f64,
+
+    /// How to downscale images before encoding: `none`, `scale:WxH`, `fit-width:W`,
+    /// `fit-height:H`, or `fit:WxH` (preserves aspect ratio, bounding the longest edge)
+    #[arg(long, default_value_t = ResizeMode::Fit(100, 100))]
+    resize: ResizeMode,
+
+    /// Write a single aggregate JSON manifest at this path instead of per-file `.bh`
+    /// sidecars. Reruns skip images whose content hash hasn't changed.
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+}
+
+/// One image's entry in a `--manifest` document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestEntry {
+    blurhash: String,
+    width: u32,
+    height: u32,
+    /// A fast, non-cryptographic hash of the source file's bytes, used to detect stale
+    /// entries on reruns instead of a cryptographic content hash.
+    content_hash: u64,
+}
+
+/// Maps a stable key (the input path) to its [`ManifestEntry`]. A `BTreeMap` keeps the
+/// serialized JSON in a deterministic order.
+type Manifest = BTreeMap<String, ManifestEntry>;
+
+fn manifest_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Whether `key` falls under one of this run's scanned input roots. A root of `.`
+/// (the current directory) scans the whole tree, so it matches everything.
+fn is_under_scanned_roots(key: &str, roots: &[PathBuf]) -> bool {
+    let path = Path::new(key);
+    roots
+        .iter()
+        .any(|root| root == Path::new(".") || path.starts_with(root))
+}
+
+/// A fixed-seed, version-stable 64-bit hash (FNV-1a) for the persisted manifest digest.
+/// `std::collections::hash_map::DefaultHasher` is explicitly documented as unstable
+/// across Rust releases, which would invalidate every `content_hash` on a toolchain
+/// upgrade and force a full re-encode.
+fn hash_content(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+async fn load_manifest(path: &Path) -> Result<Manifest> {
+    if !path.exists() {
+        return Ok(Manifest::new());
+    }
+
+    let contents = read_file_content(path).await?;
+    Ok(serde_json::from_slice(&contents)?)
+}
+
+async fn save_manifest(path: &Path, manifest: &Manifest) -> Result<()> {
+    let json = serde_json::to_string_pretty(manifest)?;
+    write_to_file(path, &json).await?;
+    Ok(())
+}
+
+/// How an image is downscaled before [`blurhash::Encoder::encode`] runs on it. BlurHash is
+/// inherently low-frequency, so shrinking the source first makes encoding far cheaper
+/// without a perceptible quality loss.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResizeMode {
+    /// Encode at the source resolution.
+    None,
+    /// Resize to an exact `(width, height)`, ignoring aspect ratio.
+    Scale(u32, u32),
+    /// Resize to an exact width, preserving aspect ratio.
+    FitWidth(u32),
+    /// Resize to an exact height, preserving aspect ratio.
+    FitHeight(u32),
+    /// Resize to fit within `(width, height)`, preserving aspect ratio.
+    Fit(u32, u32),
+}
+
+impl ResizeMode {
+    /// Applies this mode to an opened image, returning the (possibly) resized image.
+    fn apply(self, img: image::DynamicImage) -> image::DynamicImage {
+        use image::imageops::FilterType::Triangle;
+
+        match self {
+            ResizeMode::None => img,
+            ResizeMode::Scale(w, h) => img.resize_exact(w, h, Triangle),
+            ResizeMode::FitWidth(w) => img.resize(w, u32::MAX, Triangle),
+            ResizeMode::FitHeight(h) => img.resize(u32::MAX, h, Triangle),
+            ResizeMode::Fit(w, h) => img.resize(w, h, Triangle),
+        }
+    }
+
+    /// Computes the output `(width, height)` for a source image of the given size, without
+    /// decoding any pixels. Used to group same-output-size images onto a shared `Encoder`.
+    fn target_dimensions(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            ResizeMode::None => (width, height),
+            ResizeMode::Scale(w, h) => (w, h),
+            ResizeMode::FitWidth(w) => fit_dimensions(width, height, w, u32::MAX),
+            ResizeMode::FitHeight(h) => fit_dimensions(width, height, u32::MAX, h),
+            ResizeMode::Fit(w, h) => fit_dimensions(width, height, w, h),
+        }
+    }
+}
+
+/// Mirrors `image::DynamicImage::resize`'s aspect-ratio-preserving target size calculation.
+fn fit_dimensions(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    let width_ratio = f64::from(max_width) / f64::from(width);
+    let height_ratio = f64::from(max_height) / f64::from(height);
+    let ratio = f64::min(width_ratio, height_ratio);
+
+    let target_width = u32::max(1, (f64::from(width) * ratio).round() as u32);
+    let target_height = u32::max(1, (f64::from(height) * ratio).round() as u32);
+
+    (target_width, target_height)
+}
+
+impl std::str::FromStr for ResizeMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.eq_ignore_ascii_case("none") {
+            return Ok(ResizeMode::None);
+        }
+
+        let (kind, rest) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("invalid --resize value `{s}`, expected e.g. `fit:128x128`"))?;
+
+        match kind {
+            "scale" => {
+                let (w, h) = parse_wxh(rest)?;
+                Ok(ResizeMode::Scale(w, h))
+            }
+            "fit" => {
+                let (w, h) = parse_wxh(rest)?;
+                Ok(ResizeMode::Fit(w, h))
+            }
+            "fit-width" => Ok(ResizeMode::FitWidth(rest.parse()?)),
+            "fit-height" => Ok(ResizeMode::FitHeight(rest.parse()?)),
+            _ => Err(anyhow::anyhow!("unknown --resize mode `{kind}`")),
+        }
+    }
+}
+
+fn parse_wxh(s: &str) -> Result<(u32, u32)> {
+    let (w, h) = s
+        .split_once('x')
+        .ok_or_else(|| anyhow::anyhow!("expected dimensions as `WxH`, got `{s}`"))?;
+    Ok((w.parse()?, h.parse()?))
+}
+
+impl std::fmt::Display for ResizeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResizeMode::None => write!(f, "none"),
+            ResizeMode::Scale(w, h) => write!(f, "scale:{w}x{h}"),
+            ResizeMode::FitWidth(w) => write!(f, "fit-width:{w}"),
+            ResizeMode::FitHeight(h) => write!(f, "fit-height:{h}"),
+            ResizeMode::Fit(w, h) => write!(f, "fit:{w}x{h}"),
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    let image_paths = get_image_paths(&args.inputs).await?;
+    if args.decode {
+        return decode_inputs(
+            &args.inputs,
+            args.decode_width,
+            args.decode_height,
+            args.punch,
+        )
+        .await;
+    }
+
+    // In manifest mode there are no `.bh` sidecars to check for existence; the content
+    // hash decides whether an image needs re-encoding instead.
+    let skip_existing = args.manifest.is_none();
+    let all_paths = get_image_paths(&args.inputs, skip_existing).await?;
+    // Remembered so stale manifest entries for files no longer present can be pruned
+    // before saving, regardless of whether this run touched them.
+    let current_keys: HashSet<String> = all_paths.iter().map(|path| manifest_key(path)).collect();
+    // The roots this run actually scanned, so pruning only drops entries that were
+    // eligible to be seen this run — a narrower invocation (e.g. a single subdirectory)
+    // must not wipe entries for files outside its input set.
+    let scanned_roots: Vec<PathBuf> = args
+        .inputs
+        .iter()
+        .map(|input| {
+            if input.as_os_str().is_empty() || input == Path::new(".") {
+                PathBuf::from(".")
+            } else {
+                input.clone()
+            }
+        })
+        .collect();
+    let (video_paths, image_paths): (Vec<PathBuf>, Vec<PathBuf>) = all_paths
+        .into_iter()
+        .partition(|path| is_video_file(&path.to_string_lossy()));
+
+    let manifest_state: Option<Arc<Mutex<Manifest>>> = match &args.manifest {
+        Some(path) => Some(Arc::new(Mutex::new(load_manifest(path).await?))),
+        None => None,
+    };
+
+    // Probe each image's dimensions concurrently on blocking threads so a slow filesystem
+    // doesn't stall the async executor. A single unreadable image is logged and skipped
+    // rather than aborting the whole run.
+    let dimension_probes = join_all(image_paths.into_iter().map(|path| {
+        tokio::task::spawn_blocking(move || {
+            let dimensions = get_image_dimensions(&path);
+            (path, dimensions)
+        })
+    }))
+    .await;
+
+    // Group images by their post-resize dimensions so identically-sized images can share
+    // one `Encoder` instead of each recomputing the sRGB table and cosine basis.
+    let mut groups: HashMap<(usize, usize), Vec<PathBuf>> = HashMap::new();
+    for probe in dimension_probes {
+        let (path, dimensions) = probe?;
+        let (width, height) = match dimensions {
+            Ok(dimensions) => dimensions,
+            Err(e) => {
+                eprintln!("Error reading dimensions for {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let (width, height) = args.resize.target_dimensions(width, height);
+        groups
+            .entry((width as usize, height as usize))
+            .or_default()
+            .push(path);
+    }
 
     // Create a semaphore to limit concurrent tasks
     let semaphore = Arc::new(Semaphore::new(num_cpus::get()));
 
-    let tasks: Vec<_> = image_paths
-        .into_iter()
-        .map(|path| {
+    let mut tasks = Vec::new();
+
+    for path in video_paths {
+        let sem = semaphore.clone();
+        let components_x = args.components_x;
+        let components_y = args.components_y;
+        let frame_time = args.frame_time;
+        let manifest_state = manifest_state.clone();
+        tasks.push(tokio::spawn(async move {
+            let _permit = sem.acquire().await.unwrap();
+            process_video_file(path, components_x, components_y, frame_time, manifest_state).await
+        }));
+    }
+
+    for ((width, height), paths) in groups {
+        let encoder = Arc::new(blurhash::Encoder::new(
+            args.components_x,
+            args.components_y,
+            width,
+            height,
+        )?);
+
+        for path in paths {
             let sem = semaphore.clone();
-            let components_x = args.components_x;
-            let components_y = args.components_y;
-            tokio::spawn(async move {
+            let encoder = encoder.clone();
+            let skip = args.skip;
+            let resize = args.resize;
+            let manifest_state = manifest_state.clone();
+            tasks.push(tokio::spawn(async move {
                 let _permit = sem.acquire().await.unwrap();
-                process_image(path, components_x, components_y).await
-            })
-        })
-        .collect();
+                process_image(path, encoder, skip, resize, manifest_state).await
+            }));
+        }
+    }
 
     // Wait for all tasks to complete
     let results = join_all(tasks).await;
@@ -57,10 +348,20 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let (Some(manifest_path), Some(manifest_state)) = (&args.manifest, &manifest_state) {
+        let mut manifest = manifest_state.lock().await;
+        // Drop entries for files that were deleted (or moved out of the input set)
+        // since the manifest was last saved, but only within the roots this run
+        // scanned — entries from other parts of the manifest accumulate across runs.
+        manifest.retain(|key, _| current_keys.contains(key) || !is_under_scanned_roots(key, &scanned_roots));
+        save_manifest(manifest_path, &manifest).await?;
+        println!("Manifest saved to: {}", manifest_path.display());
+    }
+
     Ok(())
 }
 
-async fn get_image_paths(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+async fn get_image_paths(inputs: &[PathBuf], skip_existing: bool) -> Result<Vec<PathBuf>> {
     let mut image_paths = Vec::new();
 
     for input in inputs {
@@ -69,8 +370,8 @@ async fn get_image_paths(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
             walk_directory(".", "*", |path| {
                 let path = path.to_path_buf();
                 async move {
-                    if is_image_file(&path.to_string_lossy()) {
-                        check_and_add_image_path(&path, &mut image_paths).await?;
+                    if is_image_file(&path.to_string_lossy()) || is_video_file(&path.to_string_lossy()) {
+                        check_and_add_image_path(&path, &mut image_paths, skip_existing).await?;
                     }
                     Ok(())
                 }
@@ -80,22 +381,44 @@ async fn get_image_paths(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
             walk_directory(input, "*", |path| {
                 let path = path.to_path_buf();
                 async move {
-                    if is_image_file(&path.to_string_lossy()) {
-                        check_and_add_image_path(&path, &mut image_paths).await?;
+                    if is_image_file(&path.to_string_lossy()) || is_video_file(&path.to_string_lossy()) {
+                        check_and_add_image_path(&path, &mut image_paths, skip_existing).await?;
                     }
                     Ok(())
                 }
             })
             .await?;
-        } else if is_image_file(&input.to_string_lossy()) {
-            check_and_add_image_path(input, &mut image_paths).await?;
+        } else if is_image_file(&input.to_string_lossy()) || is_video_file(&input.to_string_lossy()) {
+            check_and_add_image_path(input, &mut image_paths, skip_existing).await?;
         }
     }
 
     Ok(image_paths)
 }
 
-async fn check_and_add_image_path(path: &Path, image_paths: &mut Vec<PathBuf>) -> Result<()> {
+fn is_video_file(path: &str) -> bool {
+    matches!(
+        Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref(),
+        Some("mp4") | Some("mkv") | Some("webm")
+    )
+}
+
+async fn check_and_add_image_path(
+    path: &Path,
+    image_paths: &mut Vec<PathBuf>,
+    skip_existing: bool,
+) -> Result<()> {
+    // In manifest mode there are no per-file `.bh` sidecars to check; every match is a
+    // candidate and the content hash decides whether it actually needs re-encoding.
+    if !skip_existing {
+        image_paths.push(path.to_path_buf());
+        return Ok(());
+    }
+
     // Generate the output filename
     let mut output_filename = path.to_path_buf();
     let new_extension = format!(
@@ -117,7 +440,17 @@ async fn check_and_add_image_path(path: &Path, image_paths: &mut Vec<PathBuf>) -
     Ok(())
 }
 
-async fn process_image(input: PathBuf, components_x: usize, components_y: usize) -> Result<()> {
+async fn process_image(
+    input: PathBuf,
+    encoder: Arc<blurhash::Encoder>,
+    skip: NonZeroU32,
+    resize: ResizeMode,
+    manifest: Option<Arc<Mutex<Manifest>>>,
+) -> Result<()> {
+    if let Some(manifest) = manifest {
+        return process_image_for_manifest(input, &encoder, skip, resize, &manifest).await;
+    }
+
     // Generate the output filename
     let mut output_filename = input.clone();
     let new_extension = format!(
@@ -136,40 +469,331 @@ async fn process_image(input: PathBuf, components_x: usize, components_y: usize)
         return Ok(());
     }
 
-    // Handle JXL files specially
+    let blurhash = compute_image_blurhash(&input, &encoder, skip, resize).await?;
+    write_to_file(&output_filename, &blurhash).await?;
+
+    println!("BlurHash saved to: {}", output_filename.display());
+
+    Ok(())
+}
+
+async fn process_image_for_manifest(
+    input: PathBuf,
+    encoder: &blurhash::Encoder,
+    skip: NonZeroU32,
+    resize: ResizeMode,
+    manifest: &Mutex<Manifest>,
+) -> Result<()> {
+    let key = manifest_key(&input);
+    let content_hash = hash_content(&tokio::fs::read(&input).await?);
+
+    let unchanged = manifest
+        .lock()
+        .await
+        .get(&key)
+        .is_some_and(|entry| entry.content_hash == content_hash);
+    if unchanged {
+        println!("Skipping {}: unchanged since last run", input.display());
+        return Ok(());
+    }
+
+    let (width, height) = get_image_dimensions(&input)?;
+    let blurhash = compute_image_blurhash(&input, encoder, skip, resize).await?;
+
+    manifest.lock().await.insert(
+        key,
+        ManifestEntry {
+            blurhash,
+            width,
+            height,
+            content_hash,
+        },
+    );
+
+    println!("BlurHash computed for: {}", input.display());
+
+    Ok(())
+}
+
+/// Runs the JXL-conversion-then-encode (or plain encode) path shared by both output
+/// modes, returning just the resulting BlurHash string.
+async fn compute_image_blurhash(
+    input: &Path,
+    encoder: &blurhash::Encoder,
+    skip: NonZeroU32,
+    resize: ResizeMode,
+) -> Result<String> {
     if is_jxl_file(&input.to_string_lossy()) {
         let temp_png = input.with_extension("png");
-        process_jxl_file(&input, Some(|_| async move { Ok(()) })).await?;
-        let blurhash = process_regular_image(&temp_png, components_x, components_y).await?;
-        write_to_file(&output_filename, &blurhash).await?;
+        process_jxl_file(input, Some(|_| async move { Ok(()) })).await?;
+        let blurhash = process_regular_image(&temp_png, encoder, skip, resize).await?;
         tokio::fs::remove_file(&temp_png).await?;
+        Ok(blurhash)
     } else {
-        let blurhash = process_regular_image(&input, components_x, components_y).await?;
-        write_to_file(&output_filename, &blurhash).await?;
+        process_regular_image(input, encoder, skip, resize).await
     }
+}
+
+async fn process_regular_image(
+    input: &Path,
+    encoder: &blurhash::Encoder,
+    skip: NonZeroU32,
+    resize: ResizeMode,
+) -> Result<String> {
+    let img = tokio::task::spawn_blocking(move || image::open(input)).await??;
+    let rgba_image = resize.apply(img).to_rgba8();
+    let pixels: Vec<u8> = rgba_image.into_raw();
+
+    Ok(encoder.encode(&pixels, skip)?)
+}
+
+async fn process_video_file(
+    input: PathBuf,
+    components_x: usize,
+    components_y: usize,
+    frame_time: f64,
+    manifest: Option<Arc<Mutex<Manifest>>>,
+) -> Result<()> {
+    if let Some(manifest) = manifest {
+        return process_video_file_for_manifest(
+            input,
+            components_x,
+            components_y,
+            frame_time,
+            &manifest,
+        )
+        .await;
+    }
+
+    // Generate the output filename
+    let mut output_filename = input.clone();
+    let new_extension = format!(
+        "{}.bh",
+        output_filename
+            .extension()
+            .unwrap_or_default()
+            .to_str()
+            .unwrap_or("")
+    );
+    output_filename.set_extension(new_extension);
+
+    // Check if the .bh file already exists
+    if output_filename.exists() {
+        println!("Skipping {}: BlurHash file already exists", input.display());
+        return Ok(());
+    }
+
+    let (blurhash, _width, _height) = tokio::task::spawn_blocking(move || {
+        extract_frame_and_encode(&input, components_x, components_y, frame_time)
+    })
+    .await??;
+    write_to_file(&output_filename, &blurhash).await?;
 
     println!("BlurHash saved to: {}", output_filename.display());
 
     Ok(())
 }
 
-async fn process_regular_image(
+async fn process_video_file_for_manifest(
+    input: PathBuf,
+    components_x: usize,
+    components_y: usize,
+    frame_time: f64,
+    manifest: &Mutex<Manifest>,
+) -> Result<()> {
+    let key = manifest_key(&input);
+    let content_hash = hash_content(&tokio::fs::read(&input).await?);
+
+    let unchanged = manifest
+        .lock()
+        .await
+        .get(&key)
+        .is_some_and(|entry| entry.content_hash == content_hash);
+    if unchanged {
+        println!("Skipping {}: unchanged since last run", input.display());
+        return Ok(());
+    }
+
+    let (blurhash, width, height) = tokio::task::spawn_blocking(move || {
+        extract_frame_and_encode(&input, components_x, components_y, frame_time)
+    })
+    .await??;
+
+    manifest.lock().await.insert(
+        key,
+        ManifestEntry {
+            blurhash,
+            width,
+            height,
+            content_hash,
+        },
+    );
+
+    println!("BlurHash computed for: {}", input.display());
+
+    Ok(())
+}
+
+/// Seeks to `frame_time` (a fraction of the video's duration) and encodes that frame's
+/// BlurHash, returning the hash alongside the extracted frame's `(width, height)`. Runs
+/// on a blocking thread since `ffmpeg-next` decoding is synchronous.
+fn extract_frame_and_encode(
     input: &Path,
     components_x: usize,
     components_y: usize,
-) -> Result<String> {
-    let img = tokio::task::spawn_blocking(move || image::open(input)).await??;
-    let (width, height) = get_image_dimensions(input)?;
-    let rgba_image = img.to_rgba8();
-    let pixels: Vec<u8> = rgba_image.into_raw();
+    frame_time: f64,
+) -> Result<(String, u32, u32)> {
+    ffmpeg::init()?;
+
+    let mut input_context = ffmpeg::format::input(&input)?;
+
+    let video_stream = input_context
+        .streams()
+        .best(ffmpeg::media::Type::Video)
+        .ok_or_else(|| anyhow::anyhow!("{}: no video stream found", input.display()))?;
+    let video_stream_index = video_stream.index();
+
+    let context_decoder = ffmpeg::codec::context::Context::from_parameters(video_stream.parameters())?;
+    let mut decoder = context_decoder.decoder().video()?;
+
+    let duration_seconds = input_context.duration() as f64 / f64::from(ffmpeg::ffi::AV_TIME_BASE);
+    let seek_seconds = (duration_seconds * frame_time.clamp(0.0, 1.0)).max(0.0);
+    let seek_timestamp = (seek_seconds * f64::from(ffmpeg::ffi::AV_TIME_BASE)) as i64;
+    input_context.seek(seek_timestamp, ..seek_timestamp)?;
+
+    let mut scaler = ffmpeg::software::scaling::context::Context::get(
+        decoder.format(),
+        decoder.width(),
+        decoder.height(),
+        ffmpeg::format::Pixel::RGBA,
+        decoder.width(),
+        decoder.height(),
+        ffmpeg::software::scaling::flag::Flags::BILINEAR,
+    )?;
+
+    let mut decoded_frame = ffmpeg::util::frame::Video::empty();
+    let mut rgba_frame = ffmpeg::util::frame::Video::empty();
+    let mut found_frame = false;
+
+    for (stream, packet) in input_context.packets() {
+        if stream.index() != video_stream_index {
+            continue;
+        }
+
+        decoder.send_packet(&packet)?;
+        if decoder.receive_frame(&mut decoded_frame).is_ok() {
+            scaler.run(&decoded_frame, &mut rgba_frame)?;
+            found_frame = true;
+            break;
+        }
+    }
+
+    if !found_frame {
+        return Err(anyhow::anyhow!(
+            "{}: could not decode a frame near the requested timestamp",
+            input.display()
+        ));
+    }
+
+    let width = rgba_frame.width();
+    let height = rgba_frame.height();
+
+    // sws pads each row to an aligned linesize, so `stride(0)` can exceed `width * 4`
+    // (e.g. any width not a multiple of 8). Copy row-by-row to get a tightly-packed
+    // buffer instead of reading padding (or under/over-reading rows) as pixel data.
+    let stride = rgba_frame.stride(0);
+    let row_bytes = width as usize * 4;
+    let data = rgba_frame.data(0);
+    let mut pixels = Vec::with_capacity(row_bytes * height as usize);
+    for row in 0..height as usize {
+        let start = row * stride;
+        pixels.extend_from_slice(&data[start..start + row_bytes]);
+    }
 
-    let blurhash = blurhash::encode(
+    let hash = blurhash::encode(
         pixels,
         components_x,
         components_y,
         width as usize,
         height as usize,
     )?;
+    Ok((hash, width, height))
+}
+
+async fn decode_inputs(inputs: &[PathBuf], width: usize, height: usize, punch: f64) -> Result<()> {
+    let bh_paths = get_bh_paths(inputs).await?;
+
+    let tasks: Vec<_> = bh_paths
+        .into_iter()
+        .map(|path| tokio::spawn(async move { process_bh_file(path, width, height, punch).await }))
+        .collect();
+
+    let results = join_all(tasks).await;
+
+    for result in results {
+        if let Err(e) = result? {
+            eprintln!("Error decoding BlurHash: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_bh_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("bh"))
+}
+
+async fn get_bh_paths(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut bh_paths = Vec::new();
+
+    for input in inputs {
+        if input.as_os_str().is_empty() || input == Path::new(".") {
+            walk_directory(".", "*", |path| {
+                let path = path.to_path_buf();
+                async move {
+                    if is_bh_file(&path.to_string_lossy()) {
+                        bh_paths.push(path);
+                    }
+                    Ok(())
+                }
+            })
+            .await?;
+        } else if input.is_dir() {
+            walk_directory(input, "*", |path| {
+                let path = path.to_path_buf();
+                async move {
+                    if is_bh_file(&path.to_string_lossy()) {
+                        bh_paths.push(path);
+                    }
+                    Ok(())
+                }
+            })
+            .await?;
+        } else if is_bh_file(&input.to_string_lossy()) {
+            bh_paths.push(input.clone());
+        }
+    }
 
-    Ok(blurhash)
+    Ok(bh_paths)
+}
+
+async fn process_bh_file(input: PathBuf, width: usize, height: usize, punch: f64) -> Result<()> {
+    let contents = read_file_content(&input).await?;
+    let hash = String::from_utf8(contents)?;
+    let hash = hash.trim();
+
+    let pixels = blurhash::decode(hash, width, height, punch)?;
+    let preview = image::RgbaImage::from_raw(width as u32, height as u32, pixels)
+        .ok_or_else(|| anyhow::anyhow!("decoded pixel buffer does not match the requested size"))?;
+
+    let output_filename = input.with_extension("preview.png");
+    let save_path = output_filename.clone();
+    tokio::task::spawn_blocking(move || preview.save(save_path)).await??;
+
+    println!("Preview saved to: {}", output_filename.display());
+
+    Ok(())
 }
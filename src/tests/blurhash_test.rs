@@ -4,6 +4,7 @@
 mod tests {
     use crate::blurhash::*;
     use image::{ImageBuffer, Rgba};
+    use std::num::NonZeroU32;
 
     #[test]
     fn test_sign_pow() {
@@ -102,4 +103,108 @@ mod tests {
         let result = encode(pixels, 4, 3, 4, 4);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_decode_hash_too_short() {
+        let result = decode("", 4, 4, 1.0);
+        assert!(matches!(result, Err(DecodingError::HashTooShort)));
+    }
+
+    #[test]
+    fn test_decode_invalid_character() {
+        // '\u{0}' is not part of the base83 alphabet.
+        let result = decode("\0\0\0\0\0\0", 4, 4, 1.0);
+        assert!(matches!(result, Err(DecodingError::InvalidCharacter)));
+    }
+
+    #[test]
+    fn test_decode_multibyte_character_does_not_panic() {
+        // A multibyte UTF-8 character pads the string past 6 bytes without being 6
+        // base83 characters; byte-slicing it should error, not panic on a non-char
+        // boundary.
+        let result = decode("0€0000", 4, 4, 1.0);
+        assert!(matches!(result, Err(DecodingError::InvalidCharacter)));
+    }
+
+    #[test]
+    fn test_decode_length_mismatch() {
+        // Size flag `0` means num_x = 1, num_y = 1, so the expected length is 6.
+        let result = decode("000000##", 4, 4, 1.0);
+        assert!(matches!(result, Err(DecodingError::LengthMismatch)));
+    }
+
+    #[test]
+    fn test_encoder_invalid_components() {
+        let result = Encoder::new(0, 1, 4, 4);
+        assert!(matches!(
+            result,
+            Err(EncodingError::ComponentsNumberInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_encoder_encode_wrong_pixel_count() {
+        let encoder = Encoder::new(4, 3, 4, 4).unwrap();
+        let too_few_pixels = vec![0u8; 4]; // A 4x4 image needs 4*4*4 bytes, not 4.
+        let result = encoder.encode(&too_few_pixels, NonZeroU32::new(1).unwrap());
+        assert!(matches!(result, Err(EncodingError::BytesPerPixelMismatch)));
+    }
+
+    #[test]
+    fn test_encoder_matches_plain_encode() {
+        let red = Rgba([255, 0, 0, 255]);
+        let pixels = create_test_image(4, 4, red);
+        let no_skip = NonZeroU32::new(1).unwrap();
+
+        let encoder = Encoder::new(4, 3, 4, 4).unwrap();
+        let hash_from_encoder = encoder.encode(&pixels, no_skip).unwrap();
+        let hash_from_encode = encode(pixels, 4, 3, 4, 4).unwrap();
+
+        assert_eq!(hash_from_encoder, hash_from_encode);
+    }
+
+    #[test]
+    fn test_encoder_reused_across_images() {
+        let encoder = Encoder::new(4, 3, 4, 4).unwrap();
+        let no_skip = NonZeroU32::new(1).unwrap();
+
+        let red = create_test_image(4, 4, Rgba([255, 0, 0, 255]));
+        let blue = create_test_image(4, 4, Rgba([0, 0, 255, 255]));
+
+        let red_hash = encoder.encode(&red, no_skip).unwrap();
+        let blue_hash = encoder.encode(&blue, no_skip).unwrap();
+
+        assert_ne!(red_hash, blue_hash);
+    }
+
+    #[test]
+    fn test_encoder_skip_samples_fewer_pixels() {
+        let pixels = create_test_image(8, 8, Rgba([200, 100, 50, 255]));
+        let encoder = Encoder::new(4, 3, 8, 8).unwrap();
+
+        let full = encoder.encode(&pixels, NonZeroU32::new(1).unwrap()).unwrap();
+        let sampled = encoder.encode(&pixels, NonZeroU32::new(2).unwrap()).unwrap();
+
+        // A solid-color image keeps the same average color (the DC term) when only every
+        // other pixel is sampled, but the AC terms average a different set of cosine
+        // samples, so the hashes as a whole differ.
+        assert_eq!(full[2..6], sampled[2..6]);
+        assert_ne!(full, sampled);
+    }
+
+    #[test]
+    fn test_decode_roundtrip_solid_color() {
+        let red = Rgba([255, 0, 0, 255]);
+        let pixels = create_test_image(4, 4, red);
+        let hash = encode(pixels, 4, 3, 4, 4).unwrap();
+
+        let decoded = decode(&hash, 4, 4, 1.0).unwrap();
+        assert_eq!(decoded.len(), 4 * 4 * 4);
+
+        // A solid red source should decode back to something close to solid red.
+        assert!(decoded[0] > 200);
+        assert!(decoded[1] < 60);
+        assert!(decoded[2] < 60);
+        assert_eq!(decoded[3], 255);
+    }
 }
@@ -5,6 +5,7 @@ mod tests {
     use crate::*;
     use anyhow::Result;
     use image::{DynamicImage, ImageBuffer, Rgb};
+    use std::sync::Arc;
     use tempfile::tempdir;
     use tokio::fs::File;
 
@@ -24,7 +25,7 @@ mod tests {
         .await?;
 
         let inputs = vec![temp_dir.path().to_path_buf()];
-        let paths = get_image_paths(&inputs).await?;
+        let paths = get_image_paths(&inputs, true).await?;
 
         assert!(!paths.is_empty());
         assert_eq!(paths.len(), 1);
@@ -59,7 +60,7 @@ mod tests {
         .await?;
 
         let inputs = vec![temp_dir.path().to_path_buf()];
-        let paths = get_image_paths(&inputs).await?;
+        let paths = get_image_paths(&inputs, true).await?;
 
         assert_eq!(paths.len(), 2); // Should only include PNG and JPG
         assert!(
@@ -101,7 +102,9 @@ mod tests {
         let img = ImageBuffer::from_pixel(2, 2, Rgb([255u8, 0, 0]));
         img.save(&test_file)?;
 
-        process_image(test_file.clone(), 4, 3).await?;
+        let encoder = Arc::new(blurhash::Encoder::new(4, 3, 2, 2)?);
+        let skip = std::num::NonZeroU32::new(1).unwrap();
+        process_image(test_file.clone(), encoder, skip, ResizeMode::None, None).await?;
 
         // Check that the .bh file was created
         let mut bh_file = test_file.clone();
@@ -120,9 +123,76 @@ mod tests {
         let img = ImageBuffer::from_pixel(2, 2, Rgb([255u8, 0, 0]));
         img.save(&test_file)?;
 
-        let blurhash = process_regular_image(&test_file, 4, 3).await?;
+        let encoder = blurhash::Encoder::new(4, 3, 2, 2)?;
+        let skip = std::num::NonZeroU32::new(1).unwrap();
+        let blurhash = process_regular_image(&test_file, &encoder, skip, ResizeMode::None).await?;
         assert!(!blurhash.is_empty());
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_process_image_manifest_mode() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.png");
+
+        let img = ImageBuffer::from_pixel(2, 2, Rgb([255u8, 0, 0]));
+        img.save(&test_file)?;
+
+        let encoder = Arc::new(blurhash::Encoder::new(4, 3, 2, 2)?);
+        let skip = std::num::NonZeroU32::new(1).unwrap();
+        let manifest = Arc::new(tokio::sync::Mutex::new(Manifest::new()));
+
+        process_image(
+            test_file.clone(),
+            encoder.clone(),
+            skip,
+            ResizeMode::None,
+            Some(manifest.clone()),
+        )
+        .await?;
+
+        // No `.bh` sidecar should be written in manifest mode.
+        let mut bh_file = test_file.clone();
+        bh_file.set_extension("png.bh");
+        assert!(!bh_file.exists());
+
+        let key = manifest_key(&test_file);
+        let entries = manifest.lock().await;
+        let entry = entries.get(&key).expect("manifest entry should be recorded");
+        assert!(!entry.blurhash.is_empty());
+        assert_eq!((entry.width, entry.height), (2, 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_mode_from_str() {
+        assert_eq!("none".parse::<ResizeMode>().unwrap(), ResizeMode::None);
+        assert_eq!(
+            "scale:64x32".parse::<ResizeMode>().unwrap(),
+            ResizeMode::Scale(64, 32)
+        );
+        assert_eq!(
+            "fit-width:128".parse::<ResizeMode>().unwrap(),
+            ResizeMode::FitWidth(128)
+        );
+        assert_eq!(
+            "fit-height:128".parse::<ResizeMode>().unwrap(),
+            ResizeMode::FitHeight(128)
+        );
+        assert_eq!(
+            "fit:100x100".parse::<ResizeMode>().unwrap(),
+            ResizeMode::Fit(100, 100)
+        );
+        assert!("bogus".parse::<ResizeMode>().is_err());
+    }
+
+    #[test]
+    fn test_resize_mode_target_dimensions() {
+        assert_eq!(ResizeMode::None.target_dimensions(640, 480), (640, 480));
+        assert_eq!(ResizeMode::Scale(64, 32).target_dimensions(640, 480), (64, 32));
+        // A 4:3 source bounded to 100x100 should keep its longest edge at 100.
+        assert_eq!(ResizeMode::Fit(100, 100).target_dimensions(800, 600), (100, 75));
+    }
 }